@@ -1,6 +1,7 @@
 // Wrapper for the Linux getmntent() API which returns a list of mountpoints
 
 use std::mem;
+use std::path::PathBuf;
 
 use std::ffi::{CStr, CString};
 
@@ -8,6 +9,8 @@ use libc::c_char;
 use libc::c_int;
 use libc::FILE;
 
+use super::MountEntry;
+
 #[repr(C)]
 #[derive(Debug)]
 struct mntent {
@@ -31,8 +34,12 @@ extern "C" {
     fn endmntent(fp: *mut FILE) -> c_int;
 }
 
-pub fn get_mount_points() -> Vec<String> {
-    let mut mount_points: Vec<String> = Vec::new();
+unsafe fn cstr_to_string(ptr: *mut c_char) -> String {
+    CStr::from_ptr(ptr).to_string_lossy().into_owned()
+}
+
+pub fn get_mount_points() -> Vec<MountEntry> {
+    let mut mount_points: Vec<MountEntry> = Vec::new();
 
     // The Linux API is somewhat baroque: rather than exposing the kernel's view of the world
     // you are expected to provide it with a mounts file which traditionally might have been
@@ -56,12 +63,20 @@ pub fn get_mount_points() -> Vec<String> {
         if mount_entry.is_null() {
             break;
         } else {
-            let mount_point = unsafe {
-                CStr::from_ptr((*mount_entry).mnt_dir)
-                    .to_string_lossy()
-                    .into_owned()
+            let (source, mount_point, fs_type, options) = unsafe {
+                (
+                    cstr_to_string((*mount_entry).mnt_fsname),
+                    cstr_to_string((*mount_entry).mnt_dir),
+                    cstr_to_string((*mount_entry).mnt_type),
+                    cstr_to_string((*mount_entry).mnt_opts),
+                )
             };
-            mount_points.push(mount_point);
+            mount_points.push(MountEntry {
+                source: PathBuf::from(source),
+                mount_point: PathBuf::from(mount_point),
+                fs_type,
+                options,
+            });
         }
     }
 
@@ -2,12 +2,15 @@
 extern crate libc;
 
 use std::ffi;
+use std::path::PathBuf;
 use std::ptr;
 use std::slice;
 use std::str;
 
 use libc::{c_int, statfs};
 
+use super::MountEntry;
+
 pub static MNT_NOWAIT: i32 = 2;
 
 extern "C" {
@@ -15,7 +18,33 @@ extern "C" {
     fn getmntinfo(mntbufp: *mut *mut statfs, flags: c_int) -> c_int;
 }
 
-pub fn get_mount_points() -> Vec<String> {
+// statfs only exposes the mount flags as a bitmask (f_flags), so we decode
+// the handful operators actually care about into the same comma-separated
+// shape `mount(8)` and /proc/mounts use for mnt_opts:
+fn decode_options(flags: u32) -> String {
+    let flag_names: &[(u32, &str)] = &[
+        (libc::MNT_RDONLY as u32, "ro"),
+        (libc::MNT_NOSUID as u32, "nosuid"),
+        (libc::MNT_NOEXEC as u32, "noexec"),
+        (libc::MNT_NODEV as u32, "nodev"),
+        (libc::MNT_SYNCHRONOUS as u32, "sync"),
+        (libc::MNT_NOATIME as u32, "noatime"),
+    ];
+
+    let mut options: Vec<&str> = flag_names
+        .iter()
+        .filter(|&&(flag, _)| flags & flag != 0)
+        .map(|&(_, name)| name)
+        .collect();
+
+    if flags & (libc::MNT_RDONLY as u32) == 0 {
+        options.insert(0, "rw");
+    }
+
+    options.join(",")
+}
+
+pub fn get_mount_points() -> Vec<MountEntry> {
     // FIXME: move this into a Darwin-specific module & implement the Linux version
     let mut raw_mounts_ptr: *mut statfs = ptr::null_mut();
 
@@ -30,9 +59,22 @@ pub fn get_mount_points() -> Vec<String> {
     mounts
         .iter()
         .map(|m| unsafe {
-            ffi::CStr::from_ptr(&m.f_mntonname[0])
+            let source = ffi::CStr::from_ptr(&m.f_mntfromname[0])
+                .to_string_lossy()
+                .into_owned();
+            let mount_point = ffi::CStr::from_ptr(&m.f_mntonname[0])
                 .to_string_lossy()
-                .into_owned()
+                .into_owned();
+            let fs_type = ffi::CStr::from_ptr(&m.f_fstypename[0])
+                .to_string_lossy()
+                .into_owned();
+
+            MountEntry {
+                source: PathBuf::from(source),
+                mount_point: PathBuf::from(mount_point),
+                fs_type,
+                options: decode_options(m.f_flags as u32),
+            }
         })
         .collect()
 }
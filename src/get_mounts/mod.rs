@@ -1,3 +1,5 @@
+use std::path::PathBuf;
+
 cfg_if! {
         if #[cfg(target_os = "linux")] {
                 mod getmntent;
@@ -7,3 +9,17 @@ cfg_if! {
                 pub use self::getmntinfo::get_mount_points;
         }
 }
+
+/// A single entry from the system mount table.
+///
+/// This keeps source/type/options grouped together the way systemd's mount
+/// parsing does, rather than throwing everything away except the mount
+/// point, so callers can make decisions (e.g. skipping virtual filesystems)
+/// without re-reading the mount table themselves.
+#[derive(Debug, Clone)]
+pub struct MountEntry {
+    pub source: PathBuf,
+    pub mount_point: PathBuf,
+    pub fs_type: String,
+    pub options: String,
+}
@@ -44,11 +44,13 @@ extern crate lazy_static;
 #[macro_use]
 extern crate prometheus;
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
 use std::process;
 use std::str;
 use std::thread;
+use std::os::unix::io::RawFd;
+use std::os::unix::process::CommandExt;
 use std::time::{Duration, Instant};
 
 use argparse::{ArgumentParser, Print, Store, StoreOption, StoreTrue};
@@ -59,6 +61,15 @@ mod errors;
 mod get_mounts;
 
 use crate::errors::*;
+use crate::get_mounts::MountEntry;
+
+// Whether a stalled check has been sent SIGTERM only, or already escalated
+// to SIGKILL, so it's signaled at most once per stage:
+#[derive(Debug)]
+enum KillStage {
+    Terminated { at: Instant },
+    Killed,
+}
 
 #[derive(Debug)]
 enum MountStatus {
@@ -68,6 +79,11 @@ enum MountStatus {
     CheckRunning {
         process: process::Child,
         start_time: Instant,
+        kill_stage: KillStage,
+        // The probe's own process group, so a timeout can signal any
+        // grandchildren it spawned rather than leaving them to block on the
+        // dead mount themselves:
+        pgid: libc::pid_t,
     },
 }
 
@@ -81,6 +97,37 @@ impl MountStatus {
     }
 }
 
+// Everything check_mounts tracks about a single mountpoint between passes.
+// fs_type and last_check_duration exist mainly to feed the per-mount
+// Prometheus labels and latency histogram without re-reading the mount
+// table at push time; source/options are carried through so health-check
+// log lines can identify which device/export is actually failing.
+struct MountState {
+    status: MountStatus,
+    source: PathBuf,
+    fs_type: String,
+    options: String,
+    last_check_duration: Duration,
+}
+
+// The probe command line to run against a mountpoint, as argv with a
+// literal `{mountpoint}` token standing in for the path to substitute.
+type ProbeTemplate = Vec<String>;
+
+// The default probe, equivalent to the previously hardcoded `/usr/bin/stat
+// <mountpoint>`, plus any per-filesystem-type overrides (e.g. a `readdir`
+// probe for NFS) selected by `MountState::fs_type`.
+struct ProbeCommands {
+    default: ProbeTemplate,
+    by_fstype: HashMap<String, ProbeTemplate>,
+}
+
+impl ProbeCommands {
+    fn template_for(&self, fs_type: &str) -> &ProbeTemplate {
+        self.by_fstype.get(fs_type).unwrap_or(&self.default)
+    }
+}
+
 quick_main! { real_main }
 
 fn real_main() -> Result<()> {
@@ -89,12 +136,24 @@ fn real_main() -> Result<()> {
         poll_interval: u64,
         prometheus_push_gateway: Option<String>,
         print_bad_mounts: bool,
+        only_fstype: Option<String>,
+        skip_fstype: Option<String>,
+        watch: bool,
+        kill_grace: u64,
+        check_command: Option<String>,
+        check_command_by_fstype: Option<String>,
     }
     let mut options = Options {
         once_only: false,
         poll_interval: 60,
         prometheus_push_gateway: None,
         print_bad_mounts: false,
+        only_fstype: None,
+        skip_fstype: None,
+        watch: false,
+        kill_grace: 10,
+        check_command: None,
+        check_command_by_fstype: None,
     };
 
     {
@@ -138,10 +197,59 @@ fn real_main() -> Result<()> {
             "Print bad mounts on standard output",
         );
 
+        ap.refer(&mut options.only_fstype).add_option(
+            &["--only-fstype"],
+            StoreOption,
+            "Comma-separated list of filesystem types to check (e.g. nfs,cifs,fuse.sshfs); all others are ignored",
+        );
+
+        ap.refer(&mut options.skip_fstype).add_option(
+            &["--skip-fstype"],
+            StoreOption,
+            "Comma-separated list of filesystem types to never check (e.g. tmpfs,proc,sysfs,cgroup)",
+        );
+
+        ap.refer(&mut options.watch).add_option(
+            &["--watch"],
+            StoreTrue,
+            "Watch the kernel mount table for changes (Linux only) and react immediately instead of waiting for the next poll",
+        );
+
+        ap.refer(&mut options.kill_grace).add_option(
+            &["--kill-grace"],
+            Store,
+            "Seconds to wait after SIGTERM before escalating a stalled check process to SIGKILL",
+        );
+
+        ap.refer(&mut options.check_command).add_option(
+            &["--check-command"],
+            StoreOption,
+            "Command template to run against a mountpoint, with {mountpoint} substituted for the path (default: /usr/bin/stat {mountpoint})",
+        );
+
+        ap.refer(&mut options.check_command_by_fstype).add_option(
+            &["--check-command-by-fstype"],
+            StoreOption,
+            "Semicolon-separated per-fstype command template overrides, e.g. 'nfs:/usr/bin/ls {mountpoint};cifs:/usr/bin/ls {mountpoint}'",
+        );
+
         ap.parse_args_or_exit();
     }
 
+    let only_fstype = parse_fstype_list(&options.only_fstype);
+    let skip_fstype = parse_fstype_list(&options.skip_fstype);
+
     let poll_interval_duration = Duration::from_secs(options.poll_interval);
+    let kill_grace_duration = Duration::from_secs(options.kill_grace);
+
+    let default_check_command = match options.check_command {
+        Some(ref template) => parse_probe_template(template)?,
+        None => vec!["/usr/bin/stat".to_string(), "{mountpoint}".to_string()],
+    };
+    let probe_commands = ProbeCommands {
+        default: default_check_command,
+        by_fstype: parse_probe_templates_by_fstype(&options.check_command_by_fstype)?,
+    };
 
     if !options.once_only {
         println!(
@@ -153,17 +261,43 @@ fn real_main() -> Result<()> {
     syslog::init_unix(syslog::Facility::LOG_USER, log::LevelFilter::Debug)
         .chain_err(|| "Unable to connect to syslog")?;
 
-    let mut mount_statuses = HashMap::<PathBuf, MountStatus>::new();
+    let mut mount_statuses = HashMap::<PathBuf, MountState>::new();
+
+    let watch_fd = if options.watch {
+        open_mount_watch_fd()
+    } else {
+        None
+    };
+
+    // Whether the upcoming check_mounts() call should limit itself to
+    // newly-appeared mounts (woken early by --watch) rather than probing
+    // every already-known mount (the periodic liveness sweep). Starts as a
+    // full sweep; mount_statuses is empty on the first pass anyway, so every
+    // mount counts as newly-appeared regardless:
+    let mut delta_only = false;
+    let mut next_full_sweep = Instant::now() + poll_interval_duration;
 
     loop {
-        check_mounts(&mut mount_statuses, options.print_bad_mounts);
+        if !delta_only {
+            next_full_sweep = Instant::now() + poll_interval_duration;
+        }
+
+        check_mounts(
+            &mut mount_statuses,
+            options.print_bad_mounts,
+            &only_fstype,
+            &skip_fstype,
+            kill_grace_duration,
+            &probe_commands,
+            delta_only,
+        );
 
         // We calculate these values each time because a filesystem may have been
         // mounted or unmounted since the last check:
         let total_mounts = mount_statuses.len();
         let dead_mounts = mount_statuses
             .iter()
-            .filter(|&(_, status)| !status.success())
+            .filter(|&(_, state)| !state.status.success())
             .count();
 
         info!("Checked {} mounts; {} are dead", total_mounts, dead_mounts);
@@ -171,7 +305,9 @@ fn real_main() -> Result<()> {
         #[cfg(feature = "with_prometheus")]
         {
             if let Some(ref gateway_address) = options.prometheus_push_gateway {
-                if let Err(e) = push_to_prometheus(gateway_address, dead_mounts, total_mounts) {
+                if let Err(e) =
+                    push_to_prometheus(gateway_address, &mount_statuses, dead_mounts, total_mounts)
+                {
                     eprintln!("{}", e);
                 }
             }
@@ -181,24 +317,120 @@ fn real_main() -> Result<()> {
             std::process::exit(0);
         }
 
-        // Wait before checking again:
-        thread::sleep(poll_interval_duration);
+        // Wait before checking again, unless the mount table changes first.
+        // A watch-triggered wakeup only means the next pass should be
+        // delta-only; the timer keeps doing full liveness sweeps either way:
+        let now = Instant::now();
+        let until_next_sweep = if next_full_sweep > now {
+            next_full_sweep - now
+        } else {
+            Duration::from_secs(0)
+        };
+        delta_only = wait_for_mount_table_change(watch_fd, until_next_sweep);
+        if delta_only {
+            debug!("Mount table changed; re-checking new/removed mounts early");
+            // Coalesce a burst of mount/unmount events into a single re-check:
+            thread::sleep(MOUNT_TABLE_DEBOUNCE);
+        }
     }
 }
 
+// Minimum time to wait after a mount-table change notification before
+// re-checking, so a flurry of mount/unmount events (e.g. an automounter
+// walking a whole map) results in one check rather than dozens.
+const MOUNT_TABLE_DEBOUNCE: Duration = Duration::from_millis(250);
+
+#[cfg(target_os = "linux")]
+fn open_mount_watch_fd() -> Option<RawFd> {
+    use std::fs::File;
+    use std::os::unix::io::IntoRawFd;
+
+    match File::open("/proc/self/mounts") {
+        // We only ever need the raw descriptor for poll(2), so leak the
+        // File handle rather than closing it for the life of the process:
+        Ok(file) => Some(file.into_raw_fd()),
+        Err(e) => {
+            eprintln!("Unable to open /proc/self/mounts for --watch: {}", e);
+            None
+        }
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn open_mount_watch_fd() -> Option<RawFd> {
+    None
+}
+
+// Waits for the kernel mount table to change or for `timeout` to elapse,
+// whichever happens first, the way systemd watches /proc/self/mounts
+// instead of polling on a fixed timer. Returns true if woken by a mount
+// table change rather than the timeout. On platforms without a watch_fd
+// (BSD, or a failed open) this just falls back to sleeping for `timeout`.
+#[cfg(target_os = "linux")]
+fn wait_for_mount_table_change(watch_fd: Option<RawFd>, timeout: Duration) -> bool {
+    let fd = match watch_fd {
+        Some(fd) => fd,
+        None => {
+            thread::sleep(timeout);
+            return false;
+        }
+    };
+
+    let mut pollfd = libc::pollfd {
+        fd,
+        events: libc::POLLPRI | libc::POLLERR,
+        revents: 0,
+    };
+
+    let timeout_ms = timeout.as_millis().min(i64::from(libc::c_int::max_value()) as u128) as libc::c_int;
+
+    match unsafe { libc::poll(&mut pollfd, 1, timeout_ms) } {
+        0 => false,
+        n if n > 0 => true,
+        _ => {
+            eprintln!(
+                "poll() on /proc/self/mounts failed: {}",
+                std::io::Error::last_os_error()
+            );
+            thread::sleep(timeout);
+            false
+        }
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn wait_for_mount_table_change(_watch_fd: Option<RawFd>, timeout: Duration) -> bool {
+    thread::sleep(timeout);
+    false
+}
+
+#[cfg(feature = "with_prometheus")]
+lazy_static! {
+    static ref TOTAL_MOUNTS: prometheus::Gauge =
+        register_gauge!("total_mountpoints", "Total number of mountpoints").unwrap();
+    static ref DEAD_MOUNTS: prometheus::Gauge =
+        register_gauge!("dead_mountpoints", "Number of unresponsive mountpoints").unwrap();
+    static ref MOUNT_ALIVE: prometheus::GaugeVec = register_gauge_vec!(
+        "mount_alive",
+        "Whether a mountpoint is alive (1) or dead/stalled (0)",
+        &["mountpoint", "fstype"]
+    )
+    .unwrap();
+    static ref MOUNT_CHECK_DURATION: prometheus::HistogramVec = register_histogram_vec!(
+        "mount_check_duration_seconds",
+        "Wall-clock time taken by the most recent health-check of a mountpoint",
+        &["mountpoint", "fstype"]
+    )
+    .unwrap();
+}
+
 #[cfg(feature = "with_prometheus")]
 fn push_to_prometheus(
     gateway: &str,
+    mount_statuses: &HashMap<PathBuf, MountState>,
     dead_mounts: usize,
     total_mounts: usize,
 ) -> prometheus::Result<()> {
-    lazy_static! {
-        static ref TOTAL_MOUNTS: prometheus::Gauge =
-            register_gauge!("total_mountpoints", "Total number of mountpoints").unwrap();
-        static ref DEAD_MOUNTS: prometheus::Gauge =
-            register_gauge!("dead_mountpoints", "Number of unresponsive mountpoints").unwrap();
-    }
-
     let prometheus_instance = hostname::get().unwrap();
 
     // The Prometheus metrics are defined as floats so we need to convert;
@@ -208,6 +440,24 @@ fn push_to_prometheus(
     TOTAL_MOUNTS.set(total_mounts as f64);
     DEAD_MOUNTS.set(dead_mounts as f64);
 
+    for (mount_point, state) in mount_statuses.iter() {
+        let labels = [
+            mount_point.to_string_lossy().into_owned(),
+            state.fs_type.clone(),
+        ];
+        let label_values: Vec<&str> = labels.iter().map(String::as_str).collect();
+
+        MOUNT_ALIVE
+            .with_label_values(&label_values)
+            .set(if state.status.success() { 1.0 } else { 0.0 });
+
+        let check_duration_seconds = state.last_check_duration.as_secs() as f64
+            + f64::from(state.last_check_duration.subsec_nanos()) / 1e9;
+        MOUNT_CHECK_DURATION
+            .with_label_values(&label_values)
+            .observe(check_duration_seconds);
+    }
+
     prometheus::push_metrics(
         "mount_status_monitor",
         labels! {"instance".to_owned() => String::from(prometheus_instance.to_str().unwrap())},
@@ -217,28 +467,184 @@ fn push_to_prometheus(
     )
 }
 
-fn check_mounts(mount_statuses: &mut HashMap<PathBuf, MountStatus>, print_bad_mounts: bool) {
-    let mount_points = get_mounts::get_mount_points().unwrap_or_else(|err| {
-        eprintln!("Failed to retrieve a list of mount-points: {:?}", err);
-        std::process::exit(2);
+// Drops the per-mount Prometheus label series for a mountpoint that has
+// disappeared from the mount table, so the push-gateway doesn't keep
+// reporting a vanished mount forever.
+#[cfg(feature = "with_prometheus")]
+fn clear_stale_mount_metrics(mount_point: &Path, fs_type: &str) {
+    let labels = [mount_point.to_string_lossy().into_owned(), fs_type.to_string()];
+    let label_values: Vec<&str> = labels.iter().map(String::as_str).collect();
+
+    let _ = MOUNT_ALIVE.remove_label_values(&label_values);
+    let _ = MOUNT_CHECK_DURATION.remove_label_values(&label_values);
+}
+
+// Splits a `--only-fstype`/`--skip-fstype` argument into its filesystem
+// type names, so a missing option and an empty list behave the same way.
+fn parse_fstype_list(arg: &Option<String>) -> Option<Vec<String>> {
+    arg.as_ref()
+        .map(|s| {
+            s.split(',')
+                .map(|t| t.trim().to_string())
+                .filter(|t| !t.is_empty())
+                .collect::<Vec<String>>()
+        })
+        .filter(|types| !types.is_empty())
+}
+
+// Splits a `--check-command` template into argv, the way it'll actually be
+// exec'd, so a malformed template is rejected at startup rather than on the
+// first check:
+fn parse_probe_template(template: &str) -> Result<ProbeTemplate> {
+    let argv: ProbeTemplate = template.split_whitespace().map(String::from).collect();
+
+    if argv.is_empty() {
+        bail!("--check-command template must not be empty: {:?}", template);
+    }
+
+    Ok(argv)
+}
+
+// Parses a `--check-command-by-fstype` argument of the form
+// "fstype:command template;fstype2:command template" into per-fstype argv
+// templates.
+fn parse_probe_templates_by_fstype(arg: &Option<String>) -> Result<HashMap<String, ProbeTemplate>> {
+    let mut templates = HashMap::new();
+
+    let spec = match *arg {
+        Some(ref spec) => spec,
+        None => return Ok(templates),
+    };
+
+    for entry in spec.split(';') {
+        let mut fields = entry.splitn(2, ':');
+        let fs_type = fields.next().unwrap_or("").trim();
+        let template = fields.next().unwrap_or("").trim();
+
+        if fs_type.is_empty() || template.is_empty() {
+            bail!(
+                "--check-command-by-fstype entries must look like 'fstype:command': {:?}",
+                entry
+            );
+        }
+
+        templates.insert(fs_type.to_string(), parse_probe_template(template)?);
+    }
+
+    Ok(templates)
+}
+
+// Substitutes `{mountpoint}` into a probe's argv template and builds the
+// process::Command to run it, preserving the existing timeout/kill
+// semantics in check_mount.
+fn build_probe_command(argv_template: &ProbeTemplate, mount_point: &Path) -> process::Command {
+    let mount_point_str = mount_point.to_string_lossy();
+    let mut argv = argv_template
+        .iter()
+        .map(|arg| arg.replace("{mountpoint}", &mount_point_str));
+
+    let mut command = process::Command::new(argv.next().expect("template is never empty"));
+    command.args(argv);
+    command
+}
+
+fn fstype_should_be_checked(
+    fs_type: &str,
+    only_fstype: &Option<Vec<String>>,
+    skip_fstype: &Option<Vec<String>>,
+) -> bool {
+    if let Some(ref only) = *only_fstype {
+        if !only.iter().any(|t| t == fs_type) {
+            return false;
+        }
+    }
+
+    if let Some(ref skip) = *skip_fstype {
+        if skip.iter().any(|t| t == fs_type) {
+            return false;
+        }
+    }
+
+    true
+}
+
+fn check_mounts(
+    mount_statuses: &mut HashMap<PathBuf, MountState>,
+    print_bad_mounts: bool,
+    only_fstype: &Option<Vec<String>>,
+    skip_fstype: &Option<Vec<String>>,
+    kill_grace: Duration,
+    probe_commands: &ProbeCommands,
+    delta_only: bool,
+) {
+    let mount_points: Vec<MountEntry> = get_mounts::get_mount_points()
+        .into_iter()
+        .filter(|entry| fstype_should_be_checked(&entry.fs_type, only_fstype, skip_fstype))
+        .collect();
+
+    // Remove any mount status entries which are no longer in the current list of mountpoints,
+    // and drop their Prometheus label series so vanished mounts don't get reported forever:
+    #[cfg(feature = "with_prometheus")]
+    let mut removed_mounts: Vec<(PathBuf, String)> = Vec::new();
+    mount_statuses.retain(|k, _state| {
+        let still_mounted = mount_points
+            .iter()
+            .position(|entry| entry.mount_point == *k)
+            .is_some();
+        #[cfg(feature = "with_prometheus")]
+        {
+            if !still_mounted {
+                removed_mounts.push((k.clone(), _state.fs_type.clone()));
+            }
+        }
+        still_mounted
     });
 
-    // Remove any mount status entries which are no longer in the current list of mountpoints:
-    mount_statuses.retain(|ref k, _| mount_points.iter().position(|i| *i == **k).is_some());
+    #[cfg(feature = "with_prometheus")]
+    for (mount_point, fs_type) in removed_mounts {
+        clear_stale_mount_metrics(&mount_point, &fs_type);
+    }
 
-    for mount_point in mount_points {
+    // Mountpoints that weren't already tracked before this pass, i.e. ones
+    // that just appeared in the mount table. A --watch wakeup only probes
+    // these; the timer sweep probes everything regardless:
+    let mut newly_appeared: HashSet<PathBuf> = HashSet::new();
+
+    for entry in mount_points {
+        let MountEntry {
+            mount_point,
+            source,
+            fs_type,
+            options,
+        } = entry;
         mount_statuses
-            .entry(mount_point)
-            .or_insert(MountStatus::Alive);
+            .entry(mount_point.clone())
+            .and_modify(|state| {
+                state.source = source.clone();
+                state.fs_type = fs_type.clone();
+                state.options = options.clone();
+            })
+            .or_insert_with(|| {
+                newly_appeared.insert(mount_point);
+                MountState {
+                    status: MountStatus::Alive,
+                    source,
+                    fs_type,
+                    options,
+                    last_check_duration: Duration::default(),
+                }
+            });
     }
 
     mount_statuses
         .par_iter_mut()
-        .for_each(|(mount_point, mount_status)| {
+        .for_each(|(mount_point, mount_state)| {
             if let MountStatus::CheckRunning {
                 ref mut process,
                 start_time,
-            } = *mount_status
+                ref mut kill_stage,
+                pgid,
+            } = mount_state.status
             {
                 match process.try_wait() {
                     Ok(Some(status)) => {
@@ -248,6 +654,8 @@ fn check_mounts(mount_statuses: &mut HashMap<PathBuf, MountStatus>, print_bad_mo
                             status,
                             start_time.elapsed().as_secs()
                         );
+                        reap_leftover_process_group(pgid, mount_point.clone());
+                        mount_state.last_check_duration = start_time.elapsed();
                     }
                     Ok(None) => {
                         warn!(
@@ -255,6 +663,26 @@ fn check_mounts(mount_statuses: &mut HashMap<PathBuf, MountStatus>, print_bad_mo
                             mount_point.display(),
                             start_time.elapsed().as_secs()
                         );
+
+                        if let KillStage::Terminated { at } = *kill_stage {
+                            if at.elapsed() >= kill_grace {
+                                warn!(
+                                    "Check for mount {} ignored SIGTERM; escalating to SIGKILL",
+                                    mount_point.display()
+                                );
+                                // Signal the whole process group, not just the
+                                // probe's own PID, so forked grandchildren die too:
+                                if unsafe { libc::kill(-pgid, libc::SIGKILL) } != 0 {
+                                    eprintln!(
+                                        "Unable to send SIGKILL to process group {}: {}",
+                                        pgid,
+                                        std::io::Error::last_os_error()
+                                    );
+                                }
+                                *kill_stage = KillStage::Killed;
+                            }
+                        }
+
                         return;
                     }
                     Err(e) => {
@@ -266,14 +694,24 @@ fn check_mounts(mount_statuses: &mut HashMap<PathBuf, MountStatus>, print_bad_mo
                         );
                     }
                 }
+            } else if delta_only && !newly_appeared.contains(mount_point) {
+                // Steady-state liveness probing belongs to the timer sweep;
+                // a --watch wakeup only probes mounts that just appeared, so
+                // one mount/unmount event doesn't trigger a re-probe storm
+                // across every other already-healthy mount. In-flight checks
+                // handled above still get followed up regardless:
+                return;
             }
-            let new_mount_status = match check_mount(mount_point) {
+            let argv_template = probe_commands.template_for(&mount_state.fs_type);
+            let check_start = Instant::now();
+            let new_mount_status = match check_mount(mount_point, argv_template) {
                 Ok(status) => status,
                 Err(e) => {
                     eprintln!("{}", e);
                     return;
                 }
             };
+            mount_state.last_check_duration = check_start.elapsed();
 
             match new_mount_status {
                 MountStatus::CheckFailed(rc) => {
@@ -287,7 +725,13 @@ fn check_mounts(mount_statuses: &mut HashMap<PathBuf, MountStatus>, print_bad_mo
             if new_mount_status.success() {
                 debug!("Mount passed health-check: {}", mount_point.display());
             } else {
-                let msg = format!("Mount failed health-check: {}", mount_point.display());
+                let msg = format!(
+                    "Mount failed health-check: {} (source={}, fs_type={}, options={})",
+                    mount_point.display(),
+                    mount_state.source.display(),
+                    mount_state.fs_type,
+                    mount_state.options
+                );
                 eprintln!("{}", msg);
                 if print_bad_mounts {
                     println!("{}", mount_point.display())
@@ -295,18 +739,87 @@ fn check_mounts(mount_statuses: &mut HashMap<PathBuf, MountStatus>, print_bad_mo
                 error!("{}", msg);
             }
 
-            *mount_status = new_mount_status;
+            mount_state.status = new_mount_status;
         });
 }
 
-fn check_mount(mount_point: &Path) -> Result<MountStatus> {
+// Confirms a check process's whole process group has exited, not just its
+// leader, so a custom probe that forks doesn't leave grandchildren behind to
+// themselves block on the dead mount. Logs at info level when it finds (and
+// cleans up) stragglers, mirroring systemd's "left-over processes" logging.
+// How many times (and how often) to poll for a signaled process group to
+// actually disappear: grandchildren aren't our children, so we can't
+// waitpid() them and have to poll kill(pgid, 0) for existence instead.
+const REAP_CHECK_ATTEMPTS: u32 = 5;
+const REAP_CHECK_INTERVAL: Duration = Duration::from_millis(100);
+
+fn reap_leftover_process_group(pgid: libc::pid_t, mount_point: PathBuf) {
+    if unsafe { libc::kill(-pgid, 0) } != 0 {
+        return;
+    }
+
+    info!(
+        "Reaping left-over processes in group {} for mount {}",
+        pgid,
+        mount_point.display()
+    );
+    if unsafe { libc::kill(-pgid, libc::SIGKILL) } != 0 {
+        eprintln!(
+            "Unable to send SIGKILL to process group {}: {}",
+            pgid,
+            std::io::Error::last_os_error()
+        );
+    }
+
+    // A grandchild wedged in uninterruptible sleep on the same dead mount
+    // won't react to SIGKILL any faster than the original probe did, so
+    // confirming it's actually gone can block for the full
+    // REAP_CHECK_ATTEMPTS * REAP_CHECK_INTERVAL. Do that polling on its own
+    // thread rather than the rayon worker running this pass, so one stuck
+    // mount can't eat into the concurrency available for checking every
+    // other mount:
+    thread::spawn(move || {
+        for _ in 0..REAP_CHECK_ATTEMPTS {
+            thread::sleep(REAP_CHECK_INTERVAL);
+            if unsafe { libc::kill(-pgid, 0) } != 0 {
+                return;
+            }
+        }
+
+        warn!(
+            "Process group {} for mount {} still has members {} ms after SIGKILL",
+            pgid,
+            mount_point.display(),
+            REAP_CHECK_ATTEMPTS * REAP_CHECK_INTERVAL.subsec_millis()
+        );
+    });
+}
+
+fn check_mount(mount_point: &Path, argv_template: &ProbeTemplate) -> Result<MountStatus> {
     let start_time = Instant::now();
-    let mut child = process::Command::new("/usr/bin/stat")
-        .arg(mount_point)
-        .stdout(process::Stdio::null())
+
+    let mut command = build_probe_command(argv_template, mount_point);
+    command.stdout(process::Stdio::null());
+
+    // Put the probe in its own process group so a timeout can signal any
+    // grandchildren it spawns, not just this one PID:
+    unsafe {
+        command.pre_exec(|| {
+            if libc::setpgid(0, 0) != 0 {
+                return Err(std::io::Error::last_os_error());
+            }
+            Ok(())
+        });
+    }
+
+    let mut child = command
         .spawn()
         .chain_err(|| "Unable to spawn process to check mount")?;
 
+    // setpgid(0, 0) makes the child its own process group leader, so its pgid
+    // is its own pid:
+    let pgid = child.id() as libc::pid_t;
+
     // See https://github.com/rust-lang/rust/issues/18166 for why we can't make this a static value:
     let child_result = child
         .wait_timeout(Duration::from_secs(3))
@@ -317,25 +830,33 @@ fn check_mount(mount_point: &Path) -> Result<MountStatus> {
                 The process has not exited and we're not going to wait for a
                 potentially very long period of time for it to recover.
 
-                We'll attempt to clean up the check process by killing it, which
-                is defined as sending SIGKILL on Unix:
-
-                https://doc.rust-lang.org/std/process/struct.Child.html#method.kill
+                Send SIGTERM to the whole process group (escalated to SIGKILL
+                later if still running after `--kill-grace`, see KillStage).
 
                 The mount_status structure returned will include this child
                 process instance so future checks can perform a non-blocking
                 test to see whether it has finally exited:
             */
-            if let Err(err) = child.kill() {
-                eprintln!("Unable to kill process {}: {:?}", child.id(), err)
-            };
+            if unsafe { libc::kill(-pgid, libc::SIGTERM) } != 0 {
+                eprintln!(
+                    "Unable to send SIGTERM to process group {}: {}",
+                    pgid,
+                    std::io::Error::last_os_error()
+                );
+            }
 
             Ok(MountStatus::CheckRunning {
                 process: child,
                 start_time: start_time,
+                kill_stage: KillStage::Terminated {
+                    at: Instant::now(),
+                },
+                pgid,
             })
         }
         Some(exit_status) => {
+            reap_leftover_process_group(pgid, mount_point.to_path_buf());
+
             let rc = exit_status.code();
             match rc {
                 Some(0) => Ok(MountStatus::Alive),